@@ -6,8 +6,10 @@
 #[allow(dead_code)]
 use std::{error::Error, sync::Mutex};
 
-use stockfish::{StockfishEval, StockfishError};
+use selfplay::{Match, MatchResult};
+use stockfish::{EvalConstraint, Stockfish, StockfishError, StockfishEval};
 
+pub mod selfplay;
 pub mod stockfish;
 
 struct App {
@@ -28,7 +30,7 @@ impl App {
 
     fn start_evaluating(&self, fen: &str) -> Result<(), StockfishError> {
         let mut stockfish = self.stockfish.lock().unwrap();
-        stockfish.restart_evaluation(fen)
+        stockfish.restart_evaluation(fen, EvalConstraint::default())
     }
 
     fn get_evaluation(&self) -> Result<Option<StockfishEval>, StockfishError> {
@@ -40,6 +42,31 @@ impl App {
         let mut stockfish = self.stockfish.lock().unwrap();
         stockfish.stop_evaluation();
     }
+
+    fn set_multipv(&self, n: usize) {
+        let mut stockfish = self.stockfish.lock().unwrap();
+        stockfish.set_multipv(n);
+    }
+
+    fn set_skill_level(&self, level: u8) {
+        let mut stockfish = self.stockfish.lock().unwrap();
+        stockfish.set_skill_level(level);
+    }
+
+    fn set_elo(&self, elo: u32) {
+        let mut stockfish = self.stockfish.lock().unwrap();
+        stockfish.set_elo(elo);
+    }
+
+    fn set_syzygy_path(&self, path: &str, max_pieces: usize) {
+        let mut stockfish = self.stockfish.lock().unwrap();
+        stockfish.set_syzygy_path(path, max_pieces);
+    }
+
+    fn set_show_wdl(&self, enabled: bool) {
+        let mut stockfish = self.stockfish.lock().unwrap();
+        stockfish.set_show_wdl(enabled);
+    }
 }
 
 #[tauri::command]
@@ -63,13 +90,69 @@ fn stop_evaluation(state: tauri::State<App>) -> Result<(), String> {
     Ok(())
 }
 
+#[tauri::command]
+fn set_multipv(state: tauri::State<App>, n: usize) -> Result<(), String> {
+    state.set_multipv(n);
+
+    Ok(())
+}
+
+#[tauri::command]
+fn set_skill_level(state: tauri::State<App>, level: u8) -> Result<(), String> {
+    state.set_skill_level(level);
+
+    Ok(())
+}
+
+#[tauri::command]
+fn set_elo(state: tauri::State<App>, elo: u32) -> Result<(), String> {
+    state.set_elo(elo);
+
+    Ok(())
+}
+
+#[tauri::command]
+fn set_syzygy_path(
+    state: tauri::State<App>,
+    path: String,
+    max_pieces: usize,
+) -> Result<(), String> {
+    state.set_syzygy_path(&path, max_pieces);
+
+    Ok(())
+}
+
+#[tauri::command]
+fn set_show_wdl(state: tauri::State<App>, enabled: bool) -> Result<(), String> {
+    state.set_show_wdl(enabled);
+
+    Ok(())
+}
+
+#[tauri::command]
+fn play_self_play_match(fen: String) -> Result<MatchResult, String> {
+    let white = Stockfish::new("stockfish");
+    let black = Stockfish::new("stockfish");
+
+    let mut game = Match::new(white, black);
+
+    game.play(&fen, EvalConstraint::default())
+        .map_err(|e| e.to_string())
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     tauri::Builder::default()
         .manage(App::new())
         .invoke_handler(tauri::generate_handler![
             get_evaluation,
             start_evaluation,
-            stop_evaluation
+            stop_evaluation,
+            set_multipv,
+            set_skill_level,
+            set_elo,
+            set_syzygy_path,
+            set_show_wdl,
+            play_self_play_match
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
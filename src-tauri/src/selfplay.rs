@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::stockfish::{EvalConstraint, GameOutcome, Stockfish, StockfishError};
+
+/// Result of a completed self-play match.
+#[derive(Debug, Serialize)]
+pub struct MatchResult {
+    /// How the game ended.
+    pub outcome: GameOutcome,
+
+    /// Number of half-moves played.
+    pub ply_count: usize,
+
+    /// The full sequence of moves played, in UCI format.
+    pub moves: Vec<String>,
+}
+
+/// A self-play match between two independently configured `Stockfish` instances.
+pub struct Match {
+    white: Stockfish,
+    black: Stockfish,
+}
+
+impl Match {
+    /// Plies without a capture or pawn move after which a game is declared drawn under the
+    /// fifty-move rule. Tracked in plies, so 100 rather than 50.
+    const FIFTY_MOVE_RULE_PLIES: u32 = 100;
+
+    /// Number of times a position must repeat before the game is declared drawn.
+    const REPETITION_LIMIT: u32 = 3;
+
+    /// Hard ply cap, as a final backstop against `chess::Board::status()` not reporting a
+    /// game-ending condition (e.g. insufficient material) and the draw detection above somehow
+    /// missing it.
+    const MAX_PLIES: usize = 500;
+
+    pub fn new(white: Stockfish, black: Stockfish) -> Self {
+        Self { white, black }
+    }
+
+    /// Play a full game from `starting_fen`, with each ply evaluated under `constraint`.
+    ///
+    /// The game is declared a draw if it runs past the fifty-move rule, a position repeats
+    /// three times, or play runs past `MAX_PLIES` without a recognized conclusion.
+    ///
+    /// # Errors
+    /// Will return an error if either engine fails to produce an evaluation, or if an
+    /// engine's chosen move is illegal in the current position.
+    pub fn play(
+        &mut self,
+        starting_fen: &str,
+        constraint: EvalConstraint,
+    ) -> Result<MatchResult, StockfishError> {
+        let mut board =
+            chess::Board::from_str(starting_fen).map_err(|_| StockfishError::InvalidInput)?;
+
+        let mut moves = vec![];
+        let mut halfmove_clock = Self::parse_halfmove_clock(starting_fen);
+        let mut position_counts = HashMap::new();
+        *position_counts.entry(board.get_hash()).or_insert(0u32) += 1;
+
+        let outcome = loop {
+            if let Some(outcome) = Self::outcome_of(&board) {
+                break outcome;
+            }
+
+            if halfmove_clock >= Self::FIFTY_MOVE_RULE_PLIES || moves.len() >= Self::MAX_PLIES {
+                break GameOutcome::Draw;
+            }
+
+            let engine = match board.side_to_move() {
+                chess::Color::White => &mut self.white,
+                chess::Color::Black => &mut self.black,
+            };
+
+            let fen = board.to_string();
+            let eval = engine.evaluate_blocking(&fen, constraint)?;
+
+            let best_move = eval.get_best_move().ok_or(StockfishError::InvalidInput)?;
+            let chess_move =
+                chess::ChessMove::from_str(&best_move).map_err(|_| StockfishError::InvalidInput)?;
+
+            if !board.legal(chess_move) {
+                return Err(StockfishError::InvalidInput);
+            }
+
+            let is_zeroing = board.piece_on(chess_move.get_source()) == Some(chess::Piece::Pawn)
+                || board.piece_on(chess_move.get_dest()).is_some();
+
+            board = board.make_move_new(chess_move);
+            moves.push(best_move);
+
+            halfmove_clock = if is_zeroing { 0 } else { halfmove_clock + 1 };
+
+            let repetitions = position_counts.entry(board.get_hash()).or_insert(0);
+            *repetitions += 1;
+
+            if *repetitions >= Self::REPETITION_LIMIT {
+                break GameOutcome::Draw;
+            }
+
+            if let Some(outcome) = Self::outcome_of(&board) {
+                break outcome;
+            }
+        };
+
+        Ok(MatchResult {
+            outcome,
+            ply_count: moves.len(),
+            moves,
+        })
+    }
+
+    /// Parse the halfmove clock (the fifty-move rule counter) out of a FEN string, defaulting
+    /// to `0` if it's missing or malformed.
+    ///
+    /// `chess::Board` doesn't track this field itself, so it has to be read from the FEN
+    /// directly to correctly seed fifty-move rule tracking for games that don't start from the
+    /// initial position.
+    fn parse_halfmove_clock(fen: &str) -> u32 {
+        fen.split_whitespace()
+            .nth(4)
+            .and_then(|field| field.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Determine the `GameOutcome` of `board`, if the game has ended.
+    fn outcome_of(board: &chess::Board) -> Option<GameOutcome> {
+        match board.status() {
+            chess::BoardStatus::Ongoing => None,
+            chess::BoardStatus::Stalemate => Some(GameOutcome::Draw),
+            chess::BoardStatus::Checkmate => match board.side_to_move() {
+                chess::Color::White => Some(GameOutcome::BlackWin),
+                chess::Color::Black => Some(GameOutcome::WhiteWin),
+            },
+        }
+    }
+}
@@ -10,6 +10,7 @@ use std::path;
 use std::str::FromStr;
 use std::sync::mpsc;
 use std::thread;
+use std::time::{Duration, Instant};
 
 /// Summary of Stockfish's evaluation of a given position.
 #[derive(Debug, Serialize)]
@@ -25,6 +26,10 @@ pub struct StockfishEval {
 
     /// Current game outcome. `None` if game is not over, even if the position is a forced mate.
     pub outcome: Option<GameOutcome>,
+
+    /// Exact tablebase verdict for the position, if one was available from Syzygy tablebases.
+    /// `None` if the position wasn't a tablebase hit (e.g. too many pieces remain on the board).
+    pub tablebase_result: Option<TablebaseResult>,
 }
 
 impl StockfishEval {
@@ -33,6 +38,37 @@ impl StockfishEval {
     }
 }
 
+/// An exact win/draw/loss verdict, as reported by a Syzygy tablebase probe.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum Wdl {
+    Win,
+    /// A win that cannot be forced within the 50-move rule.
+    ///
+    /// Never currently constructed: distinguishing this from a plain `Win` requires a
+    /// dedicated WDL/DTZ probe, which isn't exposed via Stockfish's `info` line output.
+    CursedWin,
+    Draw,
+    /// A loss that can be held off past the 50-move rule.
+    ///
+    /// Never currently constructed: distinguishing this from a plain `Loss` requires a
+    /// dedicated WDL/DTZ probe, which isn't exposed via Stockfish's `info` line output.
+    BlessedLoss,
+    Loss,
+}
+
+/// An exact tablebase result for a position, as opposed to a heuristic NNUE evaluation.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TablebaseResult {
+    /// Win/draw/loss outcome, relative to the side to move.
+    pub wdl: Wdl,
+
+    /// Distance to zeroing (of the 50-move counter), in plies, if known.
+    ///
+    /// Always `None` for now: DTZ requires a dedicated tablebase probe, which isn't
+    /// obtainable from Stockfish's `info` line output.
+    pub dtz: Option<i32>,
+}
+
 #[derive(Debug, Clone, Copy, Serialize)]
 pub enum GameOutcome {
     /// White wins
@@ -51,9 +87,14 @@ pub struct Continuation {
 
     /// Evaluation score of the continuation.
     pub score: EvaluationScore,
+
+    /// Win/draw/loss probabilities, in per-mille, summing to `1000`. Only present when
+    /// `UCI_ShowWDL` has been enabled via `set_show_wdl`.
+    pub win_draw_loss: Option<(u16, u16, u16)>,
 }
 
 /// Constraint on how long/deeply Stockfish should evaluate a given position.
+#[derive(Debug, Clone, Copy)]
 pub enum EvalConstraint {
     MaxTimeMillis(u64),
     MaxDepth(usize),
@@ -66,6 +107,23 @@ impl Default for EvalConstraint {
     }
 }
 
+impl EvalConstraint {
+    /// Extra time allotted beyond the constraint's own deadline before giving up on waiting
+    /// for a `bestmove`, to absorb process/IPC scheduling jitter.
+    const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+    /// Time `evaluate_blocking` should wait for a `bestmove` before concluding that Stockfish
+    /// is never going to produce one (e.g. the process died).
+    ///
+    /// `MaxDepth` has no inherent time bound, so it gets a generous fixed allowance instead.
+    fn max_wait(&self) -> Duration {
+        match self {
+            EvalConstraint::MaxTimeMillis(ms) => Duration::from_millis(*ms) + Self::GRACE_PERIOD,
+            EvalConstraint::MaxDepth(_) => Duration::from_secs(300),
+        }
+    }
+}
+
 /// Stockfish evaluation metric. Either centipawn advantage or forced mate.
 #[derive(Debug, Clone, Copy, Serialize)]
 pub enum EvaluationScore {
@@ -141,6 +199,8 @@ struct SFEvalInfo {
     pub score: EvaluationScore,
     pub n_nodes: usize,
     pub multipv: usize,
+    pub n_tbhits: usize,
+    pub win_draw_loss: Option<(u16, u16, u16)>,
 }
 
 impl SFEvalInfo {
@@ -152,6 +212,8 @@ impl SFEvalInfo {
         let mut score: Option<EvaluationScore> = None;
         let mut n_nodes = 0;
         let mut multipv = 0;
+        let mut n_tbhits = 0;
+        let mut win_draw_loss = None;
 
         for (i, part) in parts.iter().enumerate() {
             match *part {
@@ -162,6 +224,13 @@ impl SFEvalInfo {
                 "multipv" => {
                     multipv = usize::from_str(parts[i + 1]).ok()?;
                 }
+                "wdl" => {
+                    win_draw_loss = Some((
+                        u16::from_str(parts[i + 1]).ok()?,
+                        u16::from_str(parts[i + 2]).ok()?,
+                        u16::from_str(parts[i + 3]).ok()?,
+                    ));
+                }
                 "score" => {
                     let score_str = parts[i + 1];
                     score = if score_str == "mate" {
@@ -175,6 +244,9 @@ impl SFEvalInfo {
                 "nodes" => {
                     n_nodes = usize::from_str(parts[i + 1]).ok()?;
                 }
+                "tbhits" => {
+                    n_tbhits = usize::from_str(parts[i + 1]).ok()?;
+                }
                 "pv" => {
                     continuation = parts[i + 1..].iter().map(|s| s.to_string()).collect();
                 }
@@ -188,6 +260,8 @@ impl SFEvalInfo {
             score: score?,
             n_nodes,
             multipv,
+            n_tbhits,
+            win_draw_loss,
         })
     }
 }
@@ -224,11 +298,17 @@ impl SFEvalOutputAccumulator {
     }
 
     /// Derive a summary evaluation from the information accumulated.
+    ///
+    /// `syzygy_max_pieces` is the largest piece count covered by the tablebases currently
+    /// configured via `set_syzygy_path`, if any; it bounds how large a position can be and
+    /// still receive an exact `tablebase_result`.
     pub fn derive_evaluation(
         &self,
-        turn: chess::Color,
+        board: &chess::Board,
         outcome: Option<GameOutcome>,
+        syzygy_max_pieces: Option<usize>,
     ) -> Option<StockfishEval> {
+        let turn = board.side_to_move();
         let info_with_largest_depth = self.info.iter().max_by_key(|x| x.depth)?;
         let depth = info_with_largest_depth.depth;
         let n_nodes = info_with_largest_depth.n_nodes;
@@ -243,23 +323,86 @@ impl SFEvalOutputAccumulator {
                     best_continuations.push(Continuation {
                         score: info.score.make_absolute(turn),
                         continuation: info.continuation.clone(),
+                        win_draw_loss: Self::make_wdl_absolute(info.win_draw_loss, turn),
                     });
                 } else {
                     best_continuations[multipv - 1] = Continuation {
                         continuation: info.continuation.clone(),
                         score: info.score.clone().make_absolute(turn),
+                        win_draw_loss: Self::make_wdl_absolute(info.win_draw_loss, turn),
                     };
                 }
             }
         }
 
+        let tablebase_result =
+            Self::tablebase_result(info_with_largest_depth, board, syzygy_max_pieces);
+
         Some(StockfishEval {
             eval_depth: depth,
             n_nodes: n_nodes,
             continuations: best_continuations,
             outcome,
+            tablebase_result,
         })
     }
+
+    /// Values returned by Stockfish are always relative to the side to move.
+    ///
+    /// This converts a `wdl` triplet to be absolute, given the relative side to move, by
+    /// swapping win and loss when it's Black to move.
+    fn make_wdl_absolute(
+        win_draw_loss: Option<(u16, u16, u16)>,
+        relative_to: chess::Color,
+    ) -> Option<(u16, u16, u16)> {
+        win_draw_loss.map(|(w, d, l)| {
+            if relative_to == chess::Color::Black {
+                (l, d, w)
+            } else {
+                (w, d, l)
+            }
+        })
+    }
+
+    /// Derive an exact tablebase verdict from `info`, if `board` (the root position, not some
+    /// node deeper in the search tree) is itself a tablebase position covered by the
+    /// currently configured Syzygy tablebases.
+    ///
+    /// `tbhits` counts probes anywhere in the search tree, so it being nonzero does NOT mean
+    /// the root position was probed — a middlegame search can rack up `tbhits` from tablebase
+    /// positions reached many plies down the principal variation. We additionally require the
+    /// root to have few enough pieces to be covered by `syzygy_max_pieces` (the largest
+    /// tablebase set actually installed at the configured `SyzygyPath`, not merely "<=7",
+    /// since a 7-man root with only 6-man tables installed is not itself a tablebase hit).
+    ///
+    /// Even then, only a `Mate` score is unambiguously exact: a `CentipawnAdvantage` score can
+    /// come from the search's own heuristic evaluation of a node well above the probed
+    /// tablebase position, so it is never reported as a tablebase result.
+    fn tablebase_result(
+        info: &SFEvalInfo,
+        board: &chess::Board,
+        syzygy_max_pieces: Option<usize>,
+    ) -> Option<TablebaseResult> {
+        if info.n_tbhits == 0 {
+            return None;
+        }
+
+        let max_pieces = syzygy_max_pieces?;
+        let n_pieces = board.combined().popcnt() as usize;
+
+        if n_pieces > max_pieces {
+            return None;
+        }
+
+        let wdl = match info.score {
+            EvaluationScore::Mate(n) if n > 0 => Wdl::Win,
+            EvaluationScore::Mate(n) if n < 0 => Wdl::Loss,
+            EvaluationScore::Mate(_) => Wdl::Draw,
+            EvaluationScore::CentipawnAdvantage(_) => return None,
+        };
+
+        Some(TablebaseResult { wdl, dtz: None })
+    }
 }
 
 #[derive(Debug, Error)]
@@ -272,6 +415,9 @@ pub enum StockfishError {
 
     #[error("Stockfish process is not evaluating.")]
     NotEvaluating,
+
+    #[error("Stockfish did not report a result before the evaluation deadline.")]
+    Timeout,
 }
 
 #[derive(Debug)]
@@ -281,6 +427,7 @@ pub struct Stockfish {
     busy: bool,
     accumulator: SFEvalOutputAccumulator,
     current_position: Option<chess::Board>,
+    syzygy_max_pieces: Option<usize>,
 }
 
 impl Stockfish {
@@ -293,6 +440,7 @@ impl Stockfish {
             busy: false,
             accumulator: SFEvalOutputAccumulator::new(),
             current_position: None,
+            syzygy_max_pieces: None,
         }
     }
 
@@ -308,7 +456,57 @@ impl Stockfish {
     }
 
     pub fn set_n_threads(&mut self, n_cores: usize) {
-        self.sf_proc_stdin_writeln(&format!("setoption name Threads value {}", n_cores));
+        self.set_option("Threads", &n_cores.to_string());
+    }
+
+    /// Set a UCI option by name, e.g. `set_option("MultiPV", "3")`.
+    ///
+    /// Blocks until Stockfish has acknowledged the option and is ready for further commands.
+    pub fn set_option(&mut self, name: &str, value: &str) {
+        self.sf_proc_stdin_writeln(format!("setoption name {} value {}", name, value));
+        self.wait_until_ready();
+    }
+
+    /// Configure the number of candidate principal variations Stockfish reports per position.
+    ///
+    /// Must be called before `restart_evaluation` to take effect.
+    pub fn set_multipv(&mut self, n: usize) {
+        self.set_option("MultiPV", &n.to_string());
+    }
+
+    /// Limit Stockfish's playing strength to a skill level from `0` (weakest) to `20` (strongest,
+    /// full strength).
+    pub fn set_skill_level(&mut self, level: u8) {
+        self.set_option("Skill Level", &level.to_string());
+    }
+
+    /// Limit Stockfish's playing strength to approximately the given Elo rating.
+    pub fn set_elo(&mut self, elo: u32) {
+        self.set_option("UCI_LimitStrength", "true");
+        self.set_option("UCI_Elo", &elo.to_string());
+    }
+
+    /// Point Stockfish at a directory of Syzygy tablebase files, enabling exact endgame
+    /// probing for positions with few enough pieces remaining.
+    ///
+    /// `max_pieces` is the largest piece count covered by the tablebases installed at `path`
+    /// (e.g. `6` for a standard 6-man set, `7` if 7-man tables are also installed). It bounds
+    /// when `get_current_evaluation`/`evaluate_blocking` results are allowed to claim an exact
+    /// `tablebase_result` — a position with more pieces than this is never a genuine hit.
+    pub fn set_syzygy_path<P: AsRef<path::Path>>(&mut self, path: P, max_pieces: usize) {
+        self.set_option("SyzygyPath", &path.as_ref().to_string_lossy());
+
+        // Probe at every depth rather than only near the leaves, so shallow searches still
+        // get an exact tablebase verdict when one is available.
+        self.set_option("SyzygyProbeDepth", "1");
+
+        self.syzygy_max_pieces = Some(max_pieces);
+    }
+
+    /// Enable or disable reporting a win/draw/loss breakdown alongside each principal
+    /// variation. When enabled, `Continuation::win_draw_loss` is populated.
+    pub fn set_show_wdl(&mut self, enabled: bool) {
+        self.set_option("UCI_ShowWDL", if enabled { "true" } else { "false" });
     }
 
     /// Stop the current evaluation, if any, and wait until the process is ready to
@@ -337,11 +535,15 @@ impl Stockfish {
         self.busy = false;
     }
 
-    /// Start an evaluation of the current board position.
+    /// Start an evaluation of the current board position, bounded by `constraint`.
     ///
     /// # Errors
     /// Will return an error if an evaluation is already running.
-    pub fn restart_evaluation(&mut self, position_fen: &str) -> Result<(), StockfishError> {
+    pub fn restart_evaluation(
+        &mut self,
+        position_fen: &str,
+        constraint: EvalConstraint,
+    ) -> Result<(), StockfishError> {
         if self.busy {
             self.stop_evaluation()
         }
@@ -351,12 +553,94 @@ impl Stockfish {
         self.set_position(position_fen)?;
         self.accumulator.clear();
 
-        self.sf_proc_stdin_writeln("go");
+        match constraint {
+            EvalConstraint::MaxDepth(depth) => {
+                self.sf_proc_stdin_writeln(format!("go depth {}", depth))
+            }
+            EvalConstraint::MaxTimeMillis(ms) => {
+                self.sf_proc_stdin_writeln(format!("go movetime {}", ms))
+            }
+        }
+
         self.set_busy();
 
         Ok(())
     }
 
+    /// Start an evaluation of `position_fen` and block until Stockfish reports a `bestmove`,
+    /// returning the resulting evaluation.
+    ///
+    /// # Errors
+    /// Will return an error if `position_fen` is not a valid position, or if no `bestmove` is
+    /// seen before the constraint's deadline elapses (e.g. the Stockfish process died). An
+    /// evaluation that is already running is stopped first.
+    pub fn evaluate_blocking(
+        &mut self,
+        position_fen: &str,
+        constraint: EvalConstraint,
+    ) -> Result<StockfishEval, StockfishError> {
+        self.restart_evaluation(position_fen, constraint)?;
+
+        let deadline = Instant::now() + constraint.max_wait();
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            if remaining.is_zero() {
+                self.stop_evaluation();
+                return Err(StockfishError::Timeout);
+            }
+
+            let line = match self.sf_proc_stdout_readline_blocking(remaining) {
+                Some(line) => line,
+                None => {
+                    self.stop_evaluation();
+                    return Err(StockfishError::Timeout);
+                }
+            };
+
+            self.accumulator.process_line(&line);
+
+            if line.starts_with("bestmove") {
+                break;
+            }
+        }
+
+        let board = self
+            .current_position
+            .as_ref()
+            .ok_or(StockfishError::InvalidInput)?;
+
+        let curr_turn = board.side_to_move();
+
+        let outcome = match board.status() {
+            chess::BoardStatus::Ongoing => None,
+            chess::BoardStatus::Stalemate => Some(GameOutcome::Draw),
+            chess::BoardStatus::Checkmate => match curr_turn {
+                chess::Color::White => Some(GameOutcome::BlackWin),
+                chess::Color::Black => Some(GameOutcome::WhiteWin),
+            },
+        };
+
+        self.set_not_busy();
+
+        let eval = match outcome {
+            Some(outcome) => StockfishEval {
+                eval_depth: 0,
+                n_nodes: 0,
+                continuations: vec![],
+                outcome: Some(outcome),
+                tablebase_result: None,
+            },
+            None => self
+                .accumulator
+                .derive_evaluation(board, outcome, self.syzygy_max_pieces)
+                .ok_or(StockfishError::InvalidInput)?,
+        };
+
+        Ok(eval)
+    }
+
     /// Get the current evaluation of the current board position.
     ///
     /// # Errors
@@ -395,9 +679,12 @@ impl Stockfish {
                     n_nodes: 0,
                     continuations: vec![],
                     outcome: Some(outcome),
+                    tablebase_result: None,
                 }))
             }
-            None => Ok(self.accumulator.derive_evaluation(curr_turn, outcome)),
+            None => Ok(self
+                .accumulator
+                .derive_evaluation(board, outcome, self.syzygy_max_pieces)),
         }
     }
 
@@ -451,6 +738,14 @@ impl Stockfish {
         }
     }
 
+    /// Blocking. Read the next line from Stockfish's stdout, waiting up to `timeout` for one
+    /// to become available.
+    ///
+    /// Returns `None` if `timeout` elapses first, e.g. because the Stockfish process died.
+    fn sf_proc_stdout_readline_blocking(&mut self, timeout: Duration) -> Option<String> {
+        self.rx.recv_timeout(timeout).ok()
+    }
+
     /// Non-blocking. Read all available lines from Stockfish's stdout.
     ///
     /// Return an empty vector if no lines are available.